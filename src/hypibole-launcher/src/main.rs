@@ -9,7 +9,8 @@ use std::io::prelude::*;
 #[derive(Deserialize)]
 struct Config {
     network: Option<Network>,
-    board: Option<Board>
+    board: Option<Board>,
+    security: Option<Security>
 }
 
 #[derive(Deserialize)]
@@ -23,7 +24,15 @@ struct Board {
     gets: Option<String>,
     sets: Option<String>,
     simgets: Option<String>,
-    simsets: Option<String>
+    simsets: Option<String>,
+    init: Option<String>,
+    safe: Option<String>
+}
+
+#[derive(Deserialize)]
+struct Security {
+    secret: Option<String>,
+    secret_file: Option<String>
 }
 
 fn main() {
@@ -69,6 +78,28 @@ fn main() {
         if let Some(simsets) = board.simsets {
             hypibole_cmd.arg("--simsets").arg(simsets);
         };
+
+        if let Some(init) = board.init {
+            hypibole_cmd.arg("--init").arg(init);
+        };
+
+        if let Some(safe) = board.safe {
+            hypibole_cmd.arg("--safe").arg(safe);
+        };
+    };
+
+    if let Some(security) = config.security {
+        let secret = if let Some(secret_file) = security.secret_file {
+            let secret = fs::read_to_string(&secret_file)
+                .expect(&format!("Unable to read \"{}\".", secret_file));
+            Some(secret.trim().to_string())
+        } else {
+            security.secret
+        };
+
+        if let Some(secret) = secret {
+            hypibole_cmd.arg("--secret").arg(secret);
+        };
     };
 
     let mut hypibole_process = hypibole_cmd.spawn()
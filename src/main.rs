@@ -1,36 +1,64 @@
 use std::{convert::Infallible, net::SocketAddr};
 use std::borrow::Cow;
-use hyper::{Body, Request, Response, Server, Uri};
+use std::error::Error as StdError;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use hyper::{Body, HeaderMap, Method, Request, Response, Server, StatusCode, Uri};
+use hyper::body::HttpBody;
 use hyper::server::conn::AddrStream;
 use hyper::service::{make_service_fn, service_fn};
 use std::result::Result;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::collections::HashSet;
 use std::collections::HashMap;
 use std::collections::BTreeMap;
 use clap::Parser;
 use rppal::gpio::{Gpio};
 use rppal::gpio::IoPin;
+use rppal::gpio::InputPin;
+use rppal::gpio::OutputPin;
 use rppal::gpio::Level;
+use rppal::gpio::Trigger;
 use core::fmt::{Debug};
 use serde::{Deserialize, Serialize};
 use atomic_refcell::AtomicRefCell;
+use bytes::Bytes;
+use tokio::sync::{broadcast, mpsc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 
 use form_urlencoded;
 
 type GpioIndex = u8;
+type HmacSha256 = Hmac<Sha256>;
 
 const PIN_INDEX_PARAM_STR: &'static str = "pin";
 const OPERATION_PARAM_STR: &'static  str = "op";
 const GET_PARAM_STR: &'static  str = "get";
 const SET_PARAM_STR: &'static  str = "set";
+const COUNT_PARAM_STR: &'static  str = "count";
+const WATCH_PARAM_STR: &'static  str = "watch";
+const PWM_PARAM_STR: &'static  str = "pwm";
 const LEVEL_PARAM_STR: &'static  str = "level";
+const RESET_PARAM_STR: &'static  str = "reset";
+const FREQ_PARAM_STR: &'static  str = "freq";
+const DUTY_PARAM_STR: &'static  str = "duty";
 const HIGH_PARAM_VALUE_STR: &'static  str = "high";
 const LOW_PARAM_VALUE_STR: &'static  str = "low";
+const TRUE_PARAM_VALUE_STR: &'static  str = "true";
+const RISING_EDGE_PARAM_STR: &'static  str = "rising";
+const FALLING_EDGE_PARAM_STR: &'static  str = "falling";
+const BOTH_EDGE_PARAM_STR: &'static  str = "both";
+const WATCH_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(50);
+const TIMESTAMP_PARAM_STR: &'static str = "ts";
+const SIGNATURE_HEADER_STR: &'static str = "x-signature";
 
 pub trait DiscreteIO {
     fn get_state(&self) -> Level;
     fn set_state(&self, level: &Level);
+    fn count(&self, reset: bool) -> u64;
 }
 
 pub struct LevelContainer {
@@ -65,6 +93,10 @@ impl DiscreteIO for SimulatedPin {
     fn set_state(&self, level: &Level) {
         self.level.borrow_mut().set(level);
     }
+
+    fn count(&self, _reset: bool) -> u64 {
+        0
+    }
 }
 
 pub struct PhysicalPin {
@@ -95,6 +127,231 @@ impl DiscreteIO for PhysicalPin {
             self.io_pin.borrow_mut().set_low();
         }
     }
+
+    fn count(&self, _reset: bool) -> u64 {
+        0
+    }
+}
+
+pub struct CounterPin {
+    io_pin: AtomicRefCell<InputPin>,
+    edge_count: Arc<AtomicU64>
+}
+
+impl CounterPin {
+    pub fn new(mut io_pin: InputPin, trigger: Trigger) -> Result<CounterPin, String> {
+        let edge_count = Arc::new(AtomicU64::new(0));
+        let callback_count = edge_count.clone();
+
+        let result = io_pin.set_async_interrupt(trigger, move |_level| {
+            callback_count.fetch_add(1, Ordering::SeqCst);
+        });
+
+        if let Err(err) = result {
+            return Err(err.to_string());
+        }
+
+        Ok(CounterPin { io_pin: AtomicRefCell::new(io_pin), edge_count })
+    }
+}
+
+impl DiscreteIO for CounterPin {
+    fn get_state(&self) -> Level {
+        if self.io_pin.borrow().is_high() {
+            Level::High
+        }
+        else {
+            Level::Low
+        }
+    }
+
+    fn set_state(&self, _level: &Level) {
+        // Counter pins are inputs; they cannot be driven.
+    }
+
+    fn count(&self, reset: bool) -> u64 {
+        if reset {
+            self.edge_count.swap(0, Ordering::SeqCst)
+        }
+        else {
+            self.edge_count.load(Ordering::SeqCst)
+        }
+    }
+}
+
+pub struct SimulatedCounterPin {
+    level: AtomicRefCell<LevelContainer>,
+    edge_count: AtomicU64
+}
+
+impl SimulatedCounterPin {
+    pub fn new() -> SimulatedCounterPin {
+        SimulatedCounterPin {
+            level: AtomicRefCell::new(LevelContainer{ level: Level::Low }),
+            edge_count: AtomicU64::new(0)
+        }
+    }
+}
+
+impl DiscreteIO for SimulatedCounterPin {
+    fn get_state(&self) -> Level {
+        self.level.borrow().get().clone()
+    }
+
+    fn set_state(&self, level: &Level) {
+        let mut level_container = self.level.borrow_mut();
+        let previous_level = level_container.get();
+        level_container.set(level);
+
+        if previous_level != *level {
+            self.edge_count.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn count(&self, reset: bool) -> u64 {
+        if reset {
+            self.edge_count.swap(0, Ordering::SeqCst)
+        }
+        else {
+            self.edge_count.load(Ordering::SeqCst)
+        }
+    }
+}
+
+// Holds a physical input pin open for the lifetime of the server so its
+// async interrupt keeps firing into `sender`; never read through directly.
+pub struct WatchPin {
+    io_pin: AtomicRefCell<InputPin>
+}
+
+impl WatchPin {
+    pub fn new(mut io_pin: InputPin, sender: broadcast::Sender<Level>) -> Result<WatchPin, String> {
+        let result = io_pin.set_async_interrupt(Trigger::Both, move |level| {
+            let _ = sender.send(level);
+        });
+
+        if let Err(err) = result {
+            return Err(err.to_string());
+        }
+
+        Ok(WatchPin { io_pin: AtomicRefCell::new(io_pin) })
+    }
+}
+
+// A chunked response body fed by an mpsc channel. hyper's `Body::wrap_stream`
+// requires the wrapped stream to be `Sync`, which an `mpsc::Receiver` backed
+// by interrupt callbacks running on rppal's internal thread cannot guarantee;
+// implementing `HttpBody` directly sidesteps that requirement.
+pub struct ChannelBody {
+    receiver: mpsc::Receiver<Bytes>
+}
+
+impl HttpBody for ChannelBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        self.receiver.poll_recv(cx).map(|data| data.map(Ok))
+    }
+
+    fn poll_trailers(self: Pin<&mut Self>, _cx: &mut TaskContext<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+}
+
+// Unifies the ordinary JSON response body with the streamed `watch` body so
+// `handle` can return a single response type for both.
+enum ResponseBody {
+    Full(Body),
+    Streamed(ChannelBody)
+}
+
+impl HttpBody for ResponseBody {
+    type Data = Bytes;
+    type Error = Box<dyn StdError + Send + Sync>;
+
+    fn poll_data(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        match self.get_mut() {
+            ResponseBody::Full(body) => Pin::new(body).poll_data(cx).map(|data| data.map(|res| res.map_err(|e| e.into()))),
+            ResponseBody::Streamed(body) => Pin::new(body).poll_data(cx).map(|data| data.map(|res| res.map_err(|e| e.into())))
+        }
+    }
+
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        match self.get_mut() {
+            ResponseBody::Full(body) => Pin::new(body).poll_trailers(cx).map(|res| res.map_err(|e| e.into())),
+            ResponseBody::Streamed(body) => Pin::new(body).poll_trailers(cx).map(|res| res.map_err(|e| e.into()))
+        }
+    }
+}
+
+pub trait PwmIO {
+    fn set_pwm(&self, frequency_hz: f64, duty_cycle: f64) -> Result<(), String>;
+    fn get_pwm(&self) -> (f64, f64);
+}
+
+pub struct PwmPin {
+    io_pin: AtomicRefCell<OutputPin>,
+    last_pwm: AtomicRefCell<(f64, f64)>
+}
+
+impl PwmPin {
+    pub fn new(io_pin: OutputPin) -> PwmPin {
+        PwmPin { io_pin: AtomicRefCell::new(io_pin), last_pwm: AtomicRefCell::new((0.0, 0.0)) }
+    }
+}
+
+impl PwmIO for PwmPin {
+    fn set_pwm(&self, frequency_hz: f64, duty_cycle: f64) -> Result<(), String> {
+        match self.io_pin.borrow_mut().set_pwm_frequency(frequency_hz, duty_cycle) {
+            Ok(()) => {
+                *self.last_pwm.borrow_mut() = (frequency_hz, duty_cycle);
+                Ok(())
+            },
+            Err(err) => Err(err.to_string())
+        }
+    }
+
+    fn get_pwm(&self) -> (f64, f64) {
+        *self.last_pwm.borrow()
+    }
+}
+
+pub struct PwmContainer {
+    frequency_hz: f64,
+    duty_cycle: f64
+}
+
+impl PwmContainer {
+    pub fn set(&mut self, frequency_hz: f64, duty_cycle: f64) {
+        self.frequency_hz = frequency_hz;
+        self.duty_cycle = duty_cycle;
+    }
+
+    pub fn get(&self) -> (f64, f64) {
+        (self.frequency_hz, self.duty_cycle)
+    }
+}
+
+pub struct SimulatedPwmPin {
+    pwm: AtomicRefCell<PwmContainer>
+}
+
+impl SimulatedPwmPin {
+    pub fn new() -> SimulatedPwmPin {
+        SimulatedPwmPin { pwm: AtomicRefCell::new(PwmContainer{ frequency_hz: 0.0, duty_cycle: 0.0 }) }
+    }
+}
+
+impl PwmIO for SimulatedPwmPin {
+    fn set_pwm(&self, frequency_hz: f64, duty_cycle: f64) -> Result<(), String> {
+        self.pwm.borrow_mut().set(frequency_hz, duty_cycle);
+        Ok(())
+    }
+
+    fn get_pwm(&self) -> (f64, f64) {
+        self.pwm.borrow().get()
+    }
 }
 
 #[derive(Clone)]
@@ -103,13 +360,27 @@ struct PinSet {
     pub set_whitelist: HashSet<GpioIndex>,
     pub get_simulated: HashSet<GpioIndex>,
     pub set_simulated: HashSet<GpioIndex>,
+    pub count_whitelist: HashSet<GpioIndex>,
+    pub count_simulated: HashSet<GpioIndex>,
+    pub counter_edges: HashMap<GpioIndex, Trigger>,
+    pub watch_whitelist: HashSet<GpioIndex>,
+    pub watch_simulated: HashSet<GpioIndex>,
+    pub pwm_whitelist: HashSet<GpioIndex>,
+    pub pwm_simulated: HashSet<GpioIndex>,
 }
 
 #[derive(Clone)]
 struct AppContext {
     pub pin_set: PinSet,
     pub physical_pin_map: Arc<HashMap<GpioIndex, PhysicalPin>>,
-    pub simulated_pin_map: Arc<HashMap<GpioIndex, SimulatedPin>>
+    pub simulated_pin_map: Arc<HashMap<GpioIndex, SimulatedPin>>,
+    pub physical_counter_pin_map: Arc<HashMap<GpioIndex, CounterPin>>,
+    pub simulated_counter_pin_map: Arc<HashMap<GpioIndex, SimulatedCounterPin>>,
+    pub watch_senders: Arc<HashMap<GpioIndex, broadcast::Sender<Level>>>,
+    pub physical_pwm_pin_map: Arc<HashMap<GpioIndex, PwmPin>>,
+    pub simulated_pwm_pin_map: Arc<HashMap<GpioIndex, SimulatedPwmPin>>,
+    pub secret: Option<Arc<String>>,
+    pub clock_skew_secs: u64
 }
 
 #[derive(Parser, Debug)]
@@ -138,12 +409,58 @@ struct Args {
     // Simulated settable pins; real pins of the same index take priority.
     #[clap(long, default_value = "")]
     simsets: String,
+
+    // Whitelist of gpio pin numbers to allow edge counting of, each optionally
+    // suffixed with ":rising", ":falling", or ":both" (default "both"), e.g. "17:rising,18".
+    #[clap(long, default_value = "")]
+    counts: String,
+
+    // Simulated edge-countable pins; real pins of the same index take priority.
+    #[clap(long, default_value = "")]
+    simcounts: String,
+
+    // Whitelist of gpio pin numbers to allow streaming watch of.
+    #[clap(long, default_value = "")]
+    watches: String,
+
+    // Simulated watchable pins; must also be in simgets/simsets to have a backing state.
+    #[clap(long, default_value = "")]
+    simwatches: String,
+
+    // Whitelist of gpio pin numbers to allow PWM output of.
+    #[clap(long, default_value = "")]
+    pwms: String,
+
+    // Simulated PWM-capable pins; real pins of the same index take priority.
+    #[clap(long, default_value = "")]
+    simpwms: String,
+
+    // Shared secret used to require an HMAC-signed `X-Signature` header on every
+    // request. When unset, requests are accepted without authentication.
+    #[clap(long)]
+    secret: Option<String>,
+
+    // Maximum allowed difference, in seconds, between a request's `ts` parameter
+    // and the server's clock before a signed request is rejected as a replay.
+    #[clap(long, default_value = "30")]
+    clock_skew: u64,
+
+    // Levels, as "pin=level" pairs, to drive output pins to at startup, e.g. "17=high,18=low".
+    #[clap(long, default_value = "")]
+    init: String,
+
+    // Levels, as "pin=level" pairs, to drive output pins to on shutdown; pins not
+    // listed here default to low.
+    #[clap(long, default_value = "")]
+    safe: String,
 }
 
 #[derive(PartialEq, Debug)]
 enum Operation {
     Get(),
-    Set(Level)
+    Set(Level),
+    Count(bool),
+    Pwm { frequency_hz: f64, duty_cycle: f64 }
 }
 
 #[derive(PartialEq, Debug)]
@@ -161,9 +478,42 @@ enum OperationStatus {
 enum OperationResult {
     Get(OperationStatus, Level, GpioIndex),
     Set(OperationStatus, GpioIndex),
+    Count(OperationStatus, u64, GpioIndex),
+    Pwm(OperationStatus, f64, f64, GpioIndex),
     Error(String)
 }
 
+#[derive(Debug)]
+enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    MethodNotAllowed(String)
+}
+
+impl ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(message) => message,
+            ApiError::Unauthorized(message) => message,
+            ApiError::Forbidden(message) => message,
+            ApiError::NotFound(message) => message,
+            ApiError::MethodNotAllowed(message) => message
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() 
 {
@@ -181,26 +531,57 @@ async fn main()
     let set_simulated = parse_gpio_list(&args.simsets)
         .expect("Error parsing simulated sets list!");
 
-    let pin_set = PinSet { 
-        get_whitelist: get_whitelist, 
-        set_whitelist: set_whitelist, 
+    let (count_whitelist, counter_edges) = parse_gpio_edge_list(&args.counts)
+        .expect("Error parsing counts list!");
+
+    let count_simulated = parse_gpio_list(&args.simcounts)
+        .expect("Error parsing simulated counts list!");
+
+    let watch_whitelist = parse_gpio_list(&args.watches)
+        .expect("Error parsing watches list!");
+
+    let watch_simulated = parse_gpio_list(&args.simwatches)
+        .expect("Error parsing simulated watches list!");
+
+    let pwm_whitelist = parse_gpio_list(&args.pwms)
+        .expect("Error parsing pwms list!");
+
+    let pwm_simulated = parse_gpio_list(&args.simpwms)
+        .expect("Error parsing simulated pwms list!");
+
+    let pin_set = PinSet {
+        get_whitelist: get_whitelist,
+        set_whitelist: set_whitelist,
         get_simulated: get_simulated,
-        set_simulated: set_simulated
+        set_simulated: set_simulated,
+        count_whitelist: count_whitelist,
+        count_simulated: count_simulated,
+        counter_edges: counter_edges,
+        watch_whitelist: watch_whitelist,
+        watch_simulated: watch_simulated,
+        pwm_whitelist: pwm_whitelist,
+        pwm_simulated: pwm_simulated
     };
 
+    let init_levels = parse_gpio_level_list(&args.init)
+        .expect("Error parsing init levels!");
+
+    let safe_levels = parse_gpio_level_list(&args.safe)
+        .expect("Error parsing safe levels!");
+
     let server_details = args.address + ":" + &args.port;
 
     let addr: SocketAddr = server_details
         .parse()
         .expect("Unable to parse socket address.");
 
-    if let Err(e) = perform_service(&pin_set, &addr).await
+    if let Err(e) = perform_service(&pin_set, &addr, args.secret, args.clock_skew, init_levels, safe_levels).await
     {
         eprintln!("Service error: {}", e);
     }
 }
 
-async fn perform_service(pin_set: &PinSet, addr: &SocketAddr) -> Result<(), String> {
+async fn perform_service(pin_set: &PinSet, addr: &SocketAddr, secret: Option<String>, clock_skew_secs: u64, init_levels: HashMap<GpioIndex, Level>, safe_levels: HashMap<GpioIndex, Level>) -> Result<(), String> {
     let mut physical_pin_map: HashMap<GpioIndex, PhysicalPin> = HashMap::new();
     if !pin_set.get_whitelist.is_empty() && !pin_set.set_whitelist.is_empty() {
         let gpio = match Gpio::new() {
@@ -225,12 +606,20 @@ async fn perform_service(pin_set: &PinSet, addr: &SocketAddr) -> Result<(), Stri
                     Err(err) => return Err(err.to_string())
                 };
 
-                let io_pin = pin.into_io(rppal::gpio::Mode::Input); 
+                let io_pin = pin.into_io(rppal::gpio::Mode::Input);
                 physical_pin_map.insert(*gpio_index, PhysicalPin::new(io_pin));
             }
         }
     }
 
+    for gpio_index in &pin_set.set_whitelist {
+        if let Some(level) = init_levels.get(gpio_index) {
+            if let Some(pin) = physical_pin_map.get(gpio_index) {
+                pin.set_state(level);
+            }
+        }
+    }
+
     let mut simulated_pin_map: HashMap<GpioIndex, SimulatedPin> = HashMap::new();
     for gpio_index in &pin_set.set_simulated {
         simulated_pin_map.insert(*gpio_index, SimulatedPin::new());
@@ -242,13 +631,138 @@ async fn perform_service(pin_set: &PinSet, addr: &SocketAddr) -> Result<(), Stri
         }
     }
 
+    let mut physical_counter_pin_map: HashMap<GpioIndex, CounterPin> = HashMap::new();
+    if !pin_set.count_whitelist.is_empty() {
+        let gpio = match Gpio::new() {
+            Ok(gpio) => gpio,
+            Err(err) => return Err(err.to_string())
+        };
+
+        for gpio_index in &pin_set.count_whitelist {
+            let pin = match gpio.get(*gpio_index) {
+                Ok(pin) => pin,
+                Err(err) => return Err(err.to_string())
+            };
+
+            let input_pin = pin.into_input();
+            let trigger = pin_set.counter_edges.get(gpio_index).copied().unwrap_or(Trigger::Both);
+            let counter_pin = match CounterPin::new(input_pin, trigger) {
+                Ok(counter_pin) => counter_pin,
+                Err(err) => return Err(err)
+            };
+
+            physical_counter_pin_map.insert(*gpio_index, counter_pin);
+        }
+    }
+
+    let mut simulated_counter_pin_map: HashMap<GpioIndex, SimulatedCounterPin> = HashMap::new();
+    for gpio_index in &pin_set.count_simulated {
+        simulated_counter_pin_map.insert(*gpio_index, SimulatedCounterPin::new());
+    }
+
     let physical_pin_map = Arc::new(physical_pin_map);
     let simulated_pin_map = Arc::new(simulated_pin_map);
+    let physical_counter_pin_map = Arc::new(physical_counter_pin_map);
+    let simulated_counter_pin_map = Arc::new(simulated_counter_pin_map);
+
+    // Kept alive for the life of the server so the interrupts they own keep firing.
+    let mut physical_watch_pins: Vec<WatchPin> = Vec::new();
+    let mut watch_senders: HashMap<GpioIndex, broadcast::Sender<Level>> = HashMap::new();
+
+    if !pin_set.watch_whitelist.is_empty() {
+        let gpio = match Gpio::new() {
+            Ok(gpio) => gpio,
+            Err(err) => return Err(err.to_string())
+        };
+
+        for gpio_index in &pin_set.watch_whitelist {
+            let pin = match gpio.get(*gpio_index) {
+                Ok(pin) => pin,
+                Err(err) => return Err(err.to_string())
+            };
+
+            let input_pin = pin.into_input();
+            let (sender, _) = broadcast::channel(16);
+            let watch_pin = match WatchPin::new(input_pin, sender.clone()) {
+                Ok(watch_pin) => watch_pin,
+                Err(err) => return Err(err)
+            };
+
+            physical_watch_pins.push(watch_pin);
+            watch_senders.insert(*gpio_index, sender);
+        }
+    }
+
+    for gpio_index in &pin_set.watch_simulated {
+        if let Some(simulated_pin) = simulated_pin_map.get(gpio_index) {
+            let (sender, _) = broadcast::channel(16);
+            watch_senders.insert(*gpio_index, sender.clone());
+
+            let mut last_level = simulated_pin.get_state();
+            let simulated_pin_map = simulated_pin_map.clone();
+            let gpio_index = *gpio_index;
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(WATCH_DEBOUNCE_INTERVAL).await;
+                    if let Some(simulated_pin) = simulated_pin_map.get(&gpio_index) {
+                        let level = simulated_pin.get_state();
+                        if level != last_level {
+                            last_level = level;
+                            let _ = sender.send(level);
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    let watch_senders = Arc::new(watch_senders);
+
+    let mut physical_pwm_pin_map: HashMap<GpioIndex, PwmPin> = HashMap::new();
+    if !pin_set.pwm_whitelist.is_empty() {
+        let gpio = match Gpio::new() {
+            Ok(gpio) => gpio,
+            Err(err) => return Err(err.to_string())
+        };
+
+        for gpio_index in &pin_set.pwm_whitelist {
+            let pin = match gpio.get(*gpio_index) {
+                Ok(pin) => pin,
+                Err(err) => return Err(err.to_string())
+            };
+
+            let output_pin = pin.into_output();
+            physical_pwm_pin_map.insert(*gpio_index, PwmPin::new(output_pin));
+        }
+    }
+
+    let mut simulated_pwm_pin_map: HashMap<GpioIndex, SimulatedPwmPin> = HashMap::new();
+    for gpio_index in &pin_set.pwm_simulated {
+        simulated_pwm_pin_map.insert(*gpio_index, SimulatedPwmPin::new());
+    }
+
+    let physical_pwm_pin_map = Arc::new(physical_pwm_pin_map);
+    let simulated_pwm_pin_map = Arc::new(simulated_pwm_pin_map);
+    let secret = secret.map(Arc::new);
+
+    // Cloned ahead of the `move` closure below, which otherwise consumes these maps.
+    let shutdown_pin_map = physical_pin_map.clone();
+    let shutdown_pwm_pin_map = physical_pwm_pin_map.clone();
+    let shutdown_set_whitelist = pin_set.set_whitelist.clone();
+    let shutdown_pwm_whitelist = pin_set.pwm_whitelist.clone();
+
     let make_service = make_service_fn(move |conn: &AddrStream| {
         let context = AppContext {
             pin_set: pin_set.clone(),
             physical_pin_map: physical_pin_map.clone(),
-            simulated_pin_map: simulated_pin_map.clone()
+            simulated_pin_map: simulated_pin_map.clone(),
+            physical_counter_pin_map: physical_counter_pin_map.clone(),
+            simulated_counter_pin_map: simulated_counter_pin_map.clone(),
+            watch_senders: watch_senders.clone(),
+            physical_pwm_pin_map: physical_pwm_pin_map.clone(),
+            simulated_pwm_pin_map: simulated_pwm_pin_map.clone(),
+            secret: secret.clone(),
+            clock_skew_secs: clock_skew_secs
         };
 
         let addr = conn.remote_addr();
@@ -261,7 +775,29 @@ async fn perform_service(pin_set: &PinSet, addr: &SocketAddr) -> Result<(), Stri
 
     let server = Server::bind(&addr).serve(make_service);
 
-    if let Err(e) = server.await 
+    let server = server.with_graceful_shutdown(async move {
+        tokio::signal::ctrl_c().await.ok();
+    });
+
+    let result = server.await;
+
+    for gpio_index in &shutdown_set_whitelist {
+        if let Some(pin) = shutdown_pin_map.get(gpio_index) {
+            let safe_level = safe_levels.get(gpio_index).copied().unwrap_or(Level::Low);
+            pin.set_state(&safe_level);
+        }
+    }
+
+    for gpio_index in &shutdown_pwm_whitelist {
+        if let Some(pin) = shutdown_pwm_pin_map.get(gpio_index) {
+            let (frequency_hz, duty_cycle) = pin.get_pwm();
+            if duty_cycle != 0.0 {
+                let _ = pin.set_pwm(frequency_hz, 0.0);
+            }
+        }
+    }
+
+    if let Err(e) = result
     {
         return Err(e.to_string());
     }
@@ -285,39 +821,107 @@ fn parse_gpio_list(gpio_list_str: &str) -> Result<HashSet<GpioIndex>, String> {
     Ok(gpio_index_set)
 }
 
+fn parse_gpio_edge_list(gpio_list_str: &str) -> Result<(HashSet<GpioIndex>, HashMap<GpioIndex, Trigger>), String> {
+    let mut gpio_index_set: HashSet<GpioIndex> = HashSet::new();
+    let mut edge_map: HashMap<GpioIndex, Trigger> = HashMap::new();
+    for substr in gpio_list_str.split(',') {
+        if !substr.is_empty() {
+            let (index_str, edge_str) = match substr.split_once(':') {
+                Some((index_str, edge_str)) => (index_str, Some(edge_str)),
+                None => (substr, None)
+            };
+
+            let gpio_index = match index_str.parse::<GpioIndex>() {
+                Ok(value) => value,
+                Err(err) => return Err(err.to_string())
+            };
+
+            let trigger = match edge_str {
+                Some(RISING_EDGE_PARAM_STR) => Trigger::RisingEdge,
+                Some(FALLING_EDGE_PARAM_STR) => Trigger::FallingEdge,
+                Some(BOTH_EDGE_PARAM_STR) => Trigger::Both,
+                Some(other) => return Err(format!("Unrecognized edge parameter: \"{}\"", other)),
+                None => Trigger::Both
+            };
+
+            gpio_index_set.insert(gpio_index);
+            edge_map.insert(gpio_index, trigger);
+        }
+    }
+
+    Ok((gpio_index_set, edge_map))
+}
+
+fn parse_gpio_level_list(gpio_level_list_str: &str) -> Result<HashMap<GpioIndex, Level>, String> {
+    let mut level_map: HashMap<GpioIndex, Level> = HashMap::new();
+    for substr in gpio_level_list_str.split(',') {
+        if !substr.is_empty() {
+            let (index_str, level_str) = match substr.split_once('=') {
+                Some((index_str, level_str)) => (index_str, level_str),
+                None => return Err(format!("Expected \"pin=level\" pair, got: \"{}\"", substr))
+            };
+
+            let gpio_index = match index_str.parse::<GpioIndex>() {
+                Ok(value) => value,
+                Err(err) => return Err(err.to_string())
+            };
+
+            let level = match level_str {
+                HIGH_PARAM_VALUE_STR => Level::High,
+                LOW_PARAM_VALUE_STR => Level::Low,
+                other => return Err(format!("Unrecognized level parameter: \"{}\"", other))
+            };
+
+            level_map.insert(gpio_index, level);
+        }
+    }
+
+    Ok(level_map)
+}
+
 
 
-fn process_uri_into_operation(uri: &Uri) -> Result<OperationArgs, String> {
+fn process_uri_into_operation(uri: &Uri) -> Result<OperationArgs, ApiError> {
     let query_str = match uri.query() {
         Some(query_str) => query_str,
-        None => return Err("No arguments in URL.".to_string())
+        None => return Err(ApiError::BadRequest("No arguments in URL.".to_string()))
     };
 
     let mut gpio_index_str: Option<Cow<str>> = None;
     let mut operation_str: Option<Cow<str>> = None;
     let mut level_str: Option<Cow<str>> = None;
+    let mut reset_str: Option<Cow<str>> = None;
+    let mut freq_str: Option<Cow<str>> = None;
+    let mut duty_str: Option<Cow<str>> = None;
     for query_pair in form_urlencoded::parse(query_str.as_bytes()) {
         let key = query_pair.0;
         let value = query_pair.1;
 
         match key.as_ref() {
-            PIN_INDEX_PARAM_STR => gpio_index_str = Some(value), 
+            PIN_INDEX_PARAM_STR => gpio_index_str = Some(value),
             OPERATION_PARAM_STR => operation_str = Some(value),
             LEVEL_PARAM_STR => level_str = Some(value),
-            _ => return Err(format!("Unrecognized query parameter: \"{}\"", key.as_ref()))
+            RESET_PARAM_STR => reset_str = Some(value),
+            FREQ_PARAM_STR => freq_str = Some(value),
+            DUTY_PARAM_STR => duty_str = Some(value),
+            TIMESTAMP_PARAM_STR => {},
+            _ => return Err(ApiError::BadRequest(format!("Unrecognized query parameter: \"{}\"", key.as_ref())))
         };
     }
 
     let gpio_index_str = gpio_index_str;
     let operation_str = operation_str;
     let level_str = level_str;
+    let reset_str = reset_str;
+    let freq_str = freq_str;
+    let duty_str = duty_str;
 
     let gpio_index = match gpio_index_str {
         Some(gpio_index_str) => match gpio_index_str.parse::<GpioIndex>() {
             Ok(gpio_index) => gpio_index,
-            Err(e) => return Err(e.to_string())
+            Err(e) => return Err(ApiError::BadRequest(e.to_string()))
         }
-        None => return Err("Did not get required GPIO index argument.".to_string())
+        None => return Err(ApiError::BadRequest("Did not get required GPIO index argument.".to_string()))
     };
 
     let op_args: OperationArgs = match operation_str {
@@ -328,22 +932,172 @@ fn process_uri_into_operation(uri: &Uri) -> Result<OperationArgs, String> {
                     Some(level_str) => match level_str.as_ref() {
                         HIGH_PARAM_VALUE_STR => Level::High,
                         LOW_PARAM_VALUE_STR => Level::Low,
-                        _ => return Err(format!("Unrecognized level parameter: \"{}\"", level_str.as_ref()))
-                    }, 
-                    None => return Err("Did not get level argument required for set.".to_string())
+                        _ => return Err(ApiError::BadRequest(format!("Unrecognized level parameter: \"{}\"", level_str.as_ref())))
+                    },
+                    None => return Err(ApiError::BadRequest("Did not get level argument required for set.".to_string()))
                 };
 
                 OperationArgs{ gpio_index: gpio_index, operation: Operation::Set(level) }
             },
-            _ => return Err(format!("Unrecognized operation parameter: \"{}\"", operation_str.as_ref()))
+            COUNT_PARAM_STR => {
+                let reset = match reset_str {
+                    Some(reset_str) => reset_str.as_ref() == TRUE_PARAM_VALUE_STR,
+                    None => false
+                };
+
+                OperationArgs{ gpio_index: gpio_index, operation: Operation::Count(reset) }
+            },
+            PWM_PARAM_STR => {
+                let frequency_hz = match freq_str {
+                    Some(freq_str) => match freq_str.as_ref().parse::<f64>() {
+                        Ok(value) => value,
+                        Err(e) => return Err(ApiError::BadRequest(e.to_string()))
+                    },
+                    None => return Err(ApiError::BadRequest("Did not get freq argument required for pwm.".to_string()))
+                };
+
+                let duty_cycle = match duty_str {
+                    Some(duty_str) => match duty_str.as_ref().parse::<f64>() {
+                        Ok(value) => value,
+                        Err(e) => return Err(ApiError::BadRequest(e.to_string()))
+                    },
+                    None => return Err(ApiError::BadRequest("Did not get duty argument required for pwm.".to_string()))
+                };
+
+                if !(0.0..=1.0).contains(&duty_cycle) {
+                    return Err(ApiError::BadRequest(format!("Duty cycle, {}, is out of range [0.0, 1.0].", duty_cycle)));
+                }
+
+                OperationArgs{ gpio_index: gpio_index, operation: Operation::Pwm{ frequency_hz: frequency_hz, duty_cycle: duty_cycle } }
+            },
+            _ => return Err(ApiError::BadRequest(format!("Unrecognized operation parameter: \"{}\"", operation_str.as_ref())))
         }
-        None => return Err("Did not get required operation argument.".to_string())
+        None => return Err(ApiError::BadRequest("Did not get required operation argument.".to_string()))
     };
 
     Ok(op_args)
 }
 
-fn perform_board_operation(op_args: &OperationArgs, context: &AppContext) -> Result<OperationResult, String> {
+#[derive(Deserialize)]
+struct JsonOperationArgs {
+    pin: GpioIndex,
+    op: String,
+    level: Option<String>,
+    reset: Option<bool>,
+    freq: Option<f64>,
+    duty: Option<f64>
+}
+
+fn json_op_to_operation_args(json_op: JsonOperationArgs) -> Result<OperationArgs, String> {
+    let operation = match json_op.op.as_str() {
+        GET_PARAM_STR => Operation::Get(),
+        SET_PARAM_STR => {
+            let level = match json_op.level {
+                Some(level_str) => match level_str.as_str() {
+                    HIGH_PARAM_VALUE_STR => Level::High,
+                    LOW_PARAM_VALUE_STR => Level::Low,
+                    _ => return Err(format!("Unrecognized level parameter: \"{}\"", level_str))
+                },
+                None => return Err("Did not get level argument required for set.".to_string())
+            };
+
+            Operation::Set(level)
+        },
+        COUNT_PARAM_STR => Operation::Count(json_op.reset.unwrap_or(false)),
+        PWM_PARAM_STR => {
+            let frequency_hz = match json_op.freq {
+                Some(frequency_hz) => frequency_hz,
+                None => return Err("Did not get freq argument required for pwm.".to_string())
+            };
+
+            let duty_cycle = match json_op.duty {
+                Some(duty_cycle) => duty_cycle,
+                None => return Err("Did not get duty argument required for pwm.".to_string())
+            };
+
+            if !(0.0..=1.0).contains(&duty_cycle) {
+                return Err(format!("Duty cycle, {}, is out of range [0.0, 1.0].", duty_cycle));
+            }
+
+            Operation::Pwm{ frequency_hz: frequency_hz, duty_cycle: duty_cycle }
+        },
+        _ => return Err(format!("Unrecognized operation parameter: \"{}\"", json_op.op))
+    };
+
+    Ok(OperationArgs{ gpio_index: json_op.pin, operation: operation })
+}
+
+fn parse_body_into_operations(body: &[u8]) -> Result<Vec<OperationArgs>, String> {
+    let json_ops: Vec<JsonOperationArgs> = match serde_json::from_slice(body) {
+        Ok(json_ops) => json_ops,
+        Err(e) => return Err(e.to_string())
+    };
+
+    json_ops.into_iter().map(json_op_to_operation_args).collect()
+}
+
+const PINS_PATH_PREFIX: &'static str = "/pins/";
+
+fn parse_pins_path(path: &str) -> Option<GpioIndex> {
+    path.strip_prefix(PINS_PATH_PREFIX)?.parse::<GpioIndex>().ok()
+}
+
+fn parse_level_query(uri: &Uri) -> Result<Level, ApiError> {
+    let query_str = uri.query()
+        .ok_or_else(|| ApiError::BadRequest("Did not get level argument required for set.".to_string()))?;
+
+    for query_pair in form_urlencoded::parse(query_str.as_bytes()) {
+        if query_pair.0.as_ref() == LEVEL_PARAM_STR {
+            return match query_pair.1.as_ref() {
+                HIGH_PARAM_VALUE_STR => Ok(Level::High),
+                LOW_PARAM_VALUE_STR => Ok(Level::Low),
+                _ => Err(ApiError::BadRequest(format!("Unrecognized level parameter: \"{}\"", query_pair.1.as_ref())))
+            };
+        }
+    }
+
+    Err(ApiError::BadRequest("Did not get level argument required for set.".to_string()))
+}
+
+// Maps `GET` and `PUT` on the RESTful `/pins/{n}` path onto the legacy
+// query-string interface's get and set operations; count and pwm are only
+// reachable through the query string.
+fn build_path_operation(method: &Method, gpio_index: GpioIndex, uri: &Uri) -> Result<OperationArgs, ApiError> {
+    match *method {
+        Method::GET => Ok(OperationArgs{ gpio_index: gpio_index, operation: Operation::Get() }),
+        Method::PUT => {
+            let level = parse_level_query(uri)?;
+            Ok(OperationArgs{ gpio_index: gpio_index, operation: Operation::Set(level) })
+        },
+        _ => Err(ApiError::MethodNotAllowed(format!("Method \"{}\" is not allowed on this path.", method)))
+    }
+}
+
+fn perform_board_operation(op_args: &OperationArgs, context: &AppContext) -> Result<OperationResult, ApiError> {
+    if let Operation::Count(_) = op_args.operation {
+        if let Some(pin) = context.physical_counter_pin_map.get(&op_args.gpio_index) {
+            return perform_counter_io(op_args, pin, &context.pin_set.count_whitelist);
+        }
+        else if let Some(pin) = context.simulated_counter_pin_map.get(&op_args.gpio_index) {
+            return perform_counter_io(op_args, pin, &context.pin_set.count_simulated);
+        }
+        else {
+            return Err(ApiError::NotFound(format!("Could not find counter pin {} in either map.", op_args.gpio_index)))
+        }
+    }
+
+    if let Operation::Pwm{ .. } = op_args.operation {
+        if let Some(pin) = context.physical_pwm_pin_map.get(&op_args.gpio_index) {
+            return perform_pwm_io(op_args, pin, &context.pin_set.pwm_whitelist);
+        }
+        else if let Some(pin) = context.simulated_pwm_pin_map.get(&op_args.gpio_index) {
+            return perform_pwm_io(op_args, pin, &context.pin_set.pwm_simulated);
+        }
+        else {
+            return Err(ApiError::NotFound(format!("Could not find pwm pin {} in either map.", op_args.gpio_index)))
+        }
+    }
+
     if let Some(pin) = context.physical_pin_map.get(&op_args.gpio_index) {
         return perform_pin_io(op_args, pin, &context.pin_set.get_whitelist, &context.pin_set.set_whitelist);
     }
@@ -351,11 +1105,46 @@ fn perform_board_operation(op_args: &OperationArgs, context: &AppContext) -> Res
         return perform_pin_io(op_args, pin, &context.pin_set.get_simulated, &context.pin_set.set_simulated);
     }
     else {
-        return Err(format!("Could not find pin {} in either map.", op_args.gpio_index))
+        return Err(ApiError::NotFound(format!("Could not find pin {} in either map.", op_args.gpio_index)))
+    }
+}
+
+fn perform_counter_io(op_args: &OperationArgs, pin: &dyn DiscreteIO, count_whitelist: &HashSet<GpioIndex>) -> Result<OperationResult, ApiError> {
+    match op_args.operation {
+        Operation::Count(reset) => {
+            if count_whitelist.contains(&op_args.gpio_index) {
+                let count = pin.count(reset);
+                return Ok(OperationResult::Count(OperationStatus::Succeeded, count, op_args.gpio_index));
+            }
+            else {
+                return Err(ApiError::Forbidden(format!("Pin, {}, is not in the count whitelist for this pin type!", op_args.gpio_index)));
+            }
+        },
+        _ => return Err(ApiError::BadRequest("perform_counter_io called with a non-count operation.".to_string()))
     }
 }
 
-fn perform_pin_io(op_args: &OperationArgs, pin: &dyn DiscreteIO, get_whitelist: &HashSet<GpioIndex>, set_whitelist: &HashSet<GpioIndex>) -> Result<OperationResult, String> {
+fn perform_pwm_io(op_args: &OperationArgs, pin: &dyn PwmIO, pwm_whitelist: &HashSet<GpioIndex>) -> Result<OperationResult, ApiError> {
+    match op_args.operation {
+        Operation::Pwm{ frequency_hz, duty_cycle } => {
+            if pwm_whitelist.contains(&op_args.gpio_index) {
+                match pin.set_pwm(frequency_hz, duty_cycle) {
+                    Ok(()) => {
+                        let (applied_frequency_hz, applied_duty_cycle) = pin.get_pwm();
+                        return Ok(OperationResult::Pwm(OperationStatus::Succeeded, applied_frequency_hz, applied_duty_cycle, op_args.gpio_index));
+                    },
+                    Err(e) => return Err(ApiError::BadRequest(e))
+                }
+            }
+            else {
+                return Err(ApiError::Forbidden(format!("Pin, {}, is not in the pwm whitelist for this pin type!", op_args.gpio_index)));
+            }
+        },
+        _ => return Err(ApiError::BadRequest("perform_pwm_io called with a non-pwm operation.".to_string()))
+    }
+}
+
+fn perform_pin_io(op_args: &OperationArgs, pin: &dyn DiscreteIO, get_whitelist: &HashSet<GpioIndex>, set_whitelist: &HashSet<GpioIndex>) -> Result<OperationResult, ApiError> {
     match op_args.operation {
         Operation::Get() => {
             if get_whitelist.contains(&op_args.gpio_index) {
@@ -363,7 +1152,7 @@ fn perform_pin_io(op_args: &OperationArgs, pin: &dyn DiscreteIO, get_whitelist:
                 return Ok(OperationResult::Get(OperationStatus::Succeeded, level, op_args.gpio_index));
             }
             else {
-                return Err(format!("Pin, {}, is not in the get whitelist for this pin type!", op_args.gpio_index));
+                return Err(ApiError::Forbidden(format!("Pin, {}, is not in the get whitelist for this pin type!", op_args.gpio_index)));
             }
         },
         Operation::Set(level) => {
@@ -372,9 +1161,11 @@ fn perform_pin_io(op_args: &OperationArgs, pin: &dyn DiscreteIO, get_whitelist:
                 return Ok(OperationResult::Set(OperationStatus::Succeeded, op_args.gpio_index));
             }
             else {
-                return Err(format!("Pin, {}, is not in the set whitelist for this pin type!", op_args.gpio_index));
+                return Err(ApiError::Forbidden(format!("Pin, {}, is not in the set whitelist for this pin type!", op_args.gpio_index)));
             }
-        }
+        },
+        Operation::Count(_) => return Err(ApiError::BadRequest("perform_pin_io called with a count operation.".to_string())),
+        Operation::Pwm{ .. } => return Err(ApiError::BadRequest("perform_pin_io called with a pwm operation.".to_string()))
     }
 }
 
@@ -406,6 +1197,19 @@ fn generate_json_response(op_result: &OperationResult) -> Result<String, String>
             json_staging_set.insert("status".to_string(), status_to_str(status));
             json_staging_set.insert("pin".to_string(), pin.to_string());
         },
+        OperationResult::Count(status, count, pin) => {
+            json_staging_set.insert("operation".to_string(), "count".to_string());
+            json_staging_set.insert("status".to_string(), status_to_str(status));
+            json_staging_set.insert("count".to_string(), count.to_string());
+            json_staging_set.insert("pin".to_string(), pin.to_string());
+        },
+        OperationResult::Pwm(status, frequency_hz, duty_cycle, pin) => {
+            json_staging_set.insert("operation".to_string(), "pwm".to_string());
+            json_staging_set.insert("status".to_string(), status_to_str(status));
+            json_staging_set.insert("freq".to_string(), frequency_hz.to_string());
+            json_staging_set.insert("duty".to_string(), duty_cycle.to_string());
+            json_staging_set.insert("pin".to_string(), pin.to_string());
+        },
         OperationResult::Error(e) => {
             json_staging_set.insert("error".to_string(), e.clone());
         }
@@ -419,27 +1223,224 @@ fn generate_json_response(op_result: &OperationResult) -> Result<String, String>
     Ok(json_respose)
 }
 
-async fn handle(context: AppContext, _addr: SocketAddr, req: Request<Body>) -> Result<Response<Body>, Infallible> {
-    let operation_result: OperationResult;
-    match process_uri_into_operation(req.uri()) {
-        Ok(op_args) => {
-            let op_result = match perform_board_operation(&op_args, &context) {
-                Ok(op_result) => op_result,
-                Err(e) => OperationResult::Error(format!("Failed to perform board operation: \"{}\"", e))
+fn parse_watch_request(uri: &Uri) -> Option<GpioIndex> {
+    let query_str = uri.query()?;
+
+    let mut gpio_index: Option<GpioIndex> = None;
+    let mut is_watch = false;
+    for query_pair in form_urlencoded::parse(query_str.as_bytes()) {
+        match query_pair.0.as_ref() {
+            PIN_INDEX_PARAM_STR => gpio_index = query_pair.1.parse::<GpioIndex>().ok(),
+            OPERATION_PARAM_STR => is_watch = query_pair.1.as_ref() == WATCH_PARAM_STR,
+            _ => {}
+        }
+    }
+
+    if is_watch { gpio_index } else { None }
+}
+
+fn handle_watch(context: &AppContext, gpio_index: GpioIndex) -> Response<ResponseBody> {
+    let sender = match context.watch_senders.get(&gpio_index) {
+        Some(sender) => sender.clone(),
+        None => {
+            let json = format!("{{\"error\":\"Pin, {}, is not in the watch whitelist.\"}}", gpio_index);
+            return build_json_response(StatusCode::FORBIDDEN, json);
+        }
+    };
+
+    let mut broadcast_receiver = sender.subscribe();
+    let (body_sender, body_receiver) = mpsc::channel::<Bytes>(16);
+
+    tokio::spawn(async move {
+        loop {
+            match broadcast_receiver.recv().await {
+                Ok(level) => {
+                    let line = format!("{{\"pin\":{},\"level\":\"{}\"}}\n", gpio_index, level_to_str(&level));
+                    if body_sender.send(Bytes::from(line)).await.is_err() {
+                        // Client disconnected: `ChannelBody`'s receiver was dropped.
+                        break;
+                    }
+                },
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break
+            }
+        }
+    });
+
+    Response::builder()
+        .header("Content-Type", "application/x-ndjson")
+        .header("Transfer-Encoding", "chunked")
+        .body(ResponseBody::Streamed(ChannelBody { receiver: body_receiver }))
+        .unwrap_or_else(|_| Response::new(ResponseBody::Full(Body::from("{\"error\":\"Failed to build watch response.\"}"))))
+}
+
+fn hex_decode(hex_str: &str) -> Option<Vec<u8>> {
+    if hex_str.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex_str.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex_str[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn parse_timestamp(uri: &Uri) -> Option<u64> {
+    let query_str = uri.query()?;
+    form_urlencoded::parse(query_str.as_bytes())
+        .find(|query_pair| query_pair.0.as_ref() == TIMESTAMP_PARAM_STR)
+        .and_then(|query_pair| query_pair.1.parse::<u64>().ok())
+}
+
+fn is_timestamp_fresh(ts: u64, clock_skew_secs: u64) -> bool {
+    let now_secs = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => return false
+    };
+
+    now_secs.abs_diff(ts) <= clock_skew_secs
+}
+
+fn verify_signature(secret: &str, message: &[u8], signature_hex: &str) -> bool {
+    let signature_bytes = match hex_decode(signature_hex) {
+        Some(signature_bytes) => signature_bytes,
+        None => return false
+    };
+
+    let mut mac = match HmacSha256::new_from_slice(secret.as_bytes()) {
+        Ok(mac) => mac,
+        Err(_) => return false
+    };
+
+    mac.update(message);
+    mac.verify_slice(&signature_bytes).is_ok()
+}
+
+// Verifies the `X-Signature` header against an HMAC-SHA256 of the request's
+// query string (or JSON body, for batch requests) with the `ts` parameter
+// bound to it, and rejects stale `ts` values to block replay.
+fn authenticate_request(secret: &str, clock_skew_secs: u64, uri: &Uri, body: &[u8], signature_header: Option<&str>) -> Result<(), ApiError> {
+    let signature_hex = signature_header
+        .ok_or_else(|| ApiError::Unauthorized("Missing X-Signature header.".to_string()))?;
+
+    let ts = parse_timestamp(uri)
+        .ok_or_else(|| ApiError::Unauthorized("Missing or malformed ts parameter.".to_string()))?;
+
+    if !is_timestamp_fresh(ts, clock_skew_secs) {
+        return Err(ApiError::Unauthorized("ts parameter is outside of the allowed clock-skew window.".to_string()));
+    }
+
+    let mut message = if body.is_empty() {
+        uri.query().unwrap_or("").as_bytes().to_vec()
+    } else {
+        body.to_vec()
+    };
+    message.extend_from_slice(ts.to_string().as_bytes());
+
+    if !verify_signature(secret, &message, signature_hex) {
+        return Err(ApiError::Unauthorized("Signature verification failed.".to_string()));
+    }
+
+    Ok(())
+}
+
+fn is_json_content_type(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("application/json"))
+        .unwrap_or(false)
+}
+
+fn handle_batch(context: &AppContext, body_bytes: &[u8]) -> Response<ResponseBody> {
+    let op_args_list = match parse_body_into_operations(body_bytes) {
+        Ok(op_args_list) => op_args_list,
+        Err(e) => {
+            let json = match generate_json_response(&OperationResult::Error(e)) {
+                Ok(json) => json,
+                Err(e) => format!("{{ \"Error\": \"{}\" }}", e.to_string())
             };
+            return build_json_response(StatusCode::BAD_REQUEST, json);
+        }
+    };
 
-            operation_result = op_result;
-        },
+    let mut result_jsons: Vec<String> = Vec::new();
+    for op_args in &op_args_list {
+        let op_result = match perform_board_operation(op_args, context) {
+            Ok(op_result) => op_result,
+            Err(e) => OperationResult::Error(format!("Failed to perform board operation: \"{}\"", e.message()))
+        };
+
+        let json = match generate_json_response(&op_result) {
+            Ok(json) => json,
+            Err(e) => format!("{{ \"Error\": \"{}\" }}", e.to_string())
+        };
+
+        result_jsons.push(json);
+    }
+
+    build_json_response(StatusCode::OK, format!("[{}]", result_jsons.join(",")))
+}
+
+fn build_json_response(status: StatusCode, json: String) -> Response<ResponseBody> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(ResponseBody::Full(Body::from(json)))
+        .unwrap_or_else(|_| Response::new(ResponseBody::Full(Body::from("{\"error\":\"Failed to build response.\"}"))))
+}
+
+async fn handle(context: AppContext, _addr: SocketAddr, req: Request<Body>) -> Result<Response<ResponseBody>, Infallible> {
+    let is_batch_request = req.method() == Method::POST && is_json_content_type(&req);
+
+    let (parts, body) = req.into_parts();
+    let body_bytes = if is_batch_request {
+        match hyper::body::to_bytes(body).await {
+            Ok(body_bytes) => body_bytes,
+            Err(e) => {
+                let json = format!("{{\"error\":\"Failed to read request body: \\\"{}\\\"\"}}", e);
+                return Ok(build_json_response(StatusCode::BAD_REQUEST, json));
+            }
+        }
+    } else {
+        Bytes::new()
+    };
+
+    if let Some(secret) = &context.secret {
+        let signature_header = parts.headers.get(SIGNATURE_HEADER_STR).and_then(|value| value.to_str().ok());
+        if let Err(e) = authenticate_request(secret.as_str(), context.clock_skew_secs, &parts.uri, &body_bytes, signature_header) {
+            return Ok(build_json_response(e.status_code(), format!("{{\"error\":\"{}\"}}", e.message())));
+        }
+    }
 
-        Err(e) => operation_result = OperationResult::Error(e.to_string())
+    if let Some(gpio_index) = parse_watch_request(&parts.uri) {
+        return Ok(handle_watch(&context, gpio_index));
     }
 
+    if is_batch_request {
+        return Ok(handle_batch(&context, &body_bytes));
+    }
+
+    let op_args_result = match parse_pins_path(parts.uri.path()) {
+        Some(gpio_index) => build_path_operation(&parts.method, gpio_index, &parts.uri),
+        None if parts.uri.path() == "/" => process_uri_into_operation(&parts.uri),
+        None => Err(ApiError::NotFound(format!("No route for path \"{}\".", parts.uri.path())))
+    };
+
+    let (status, operation_result) = match op_args_result {
+        Ok(op_args) => match perform_board_operation(&op_args, &context) {
+            Ok(op_result) => (StatusCode::OK, op_result),
+            Err(e) => (e.status_code(), OperationResult::Error(e.message().to_string()))
+        },
+        Err(e) => (e.status_code(), OperationResult::Error(e.message().to_string()))
+    };
+
     let json_respose = match generate_json_response(&operation_result) {
         Ok(json) => json,
         Err(e) => format!("{{ \"Error\": \"{}\" }}", e.to_string())
     };
 
-    Ok(Response::new(Body::from(json_respose)))
+    Ok(build_json_response(status, json_respose))
 }
 
 #[cfg(test)]
@@ -487,7 +1488,7 @@ mod tests {
         for bad_uri in bad_uris {
             match process_uri_into_operation(bad_uri) {
                 Ok(_) => panic!("These calls should never succeed!"),
-                Err(e) => println!("Got expected error: \"{}\"", e)
+                Err(e) => println!("Got expected error: {:?}", e)
             }
         }
     }
@@ -509,4 +1510,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_gpio_edge_list() {
+        let (gpio_index_set, edge_map) = parse_gpio_edge_list("17:rising,18,19:falling,20:both")
+            .expect("parse_gpio_edge_list function failed!");
+
+        let expected_set: HashSet<GpioIndex> = HashSet::from([17, 18, 19, 20]);
+        assert_eq!(expected_set, gpio_index_set);
+
+        assert_eq!(Trigger::RisingEdge, edge_map[&17]);
+        assert_eq!(Trigger::Both, edge_map[&18]);
+        assert_eq!(Trigger::FallingEdge, edge_map[&19]);
+        assert_eq!(Trigger::Both, edge_map[&20]);
+
+        match parse_gpio_edge_list("17:sideways") {
+            Ok(_) => panic!("This call should never succeed!"),
+            Err(e) => println!("Got expected error: \"{}\"", e)
+        }
+    }
+
+    #[test]
+    fn test_simulated_counter_pin() {
+        let simulated_counter_pin = SimulatedCounterPin::new();
+        for _ in 0..10 {
+            simulated_counter_pin.set_state(&Level::High);
+            simulated_counter_pin.set_state(&Level::Low);
+        }
+
+        assert_eq!(20, simulated_counter_pin.count(false));
+        assert_eq!(20, simulated_counter_pin.count(true));
+        assert_eq!(0, simulated_counter_pin.count(false));
+
+        let simulated_counter_pin = SimulatedCounterPin::new();
+        simulated_counter_pin.set_state(&Level::Low);
+        simulated_counter_pin.set_state(&Level::Low);
+        simulated_counter_pin.set_state(&Level::High);
+        simulated_counter_pin.set_state(&Level::High);
+
+        assert_eq!(1, simulated_counter_pin.count(false));
+    }
+
+    #[test]
+    fn test_parse_body_into_operations() {
+        let body = br#"[{"pin":1,"op":"set","level":"high"},{"pin":2,"op":"get"},{"pin":3,"op":"count","reset":true}]"#;
+
+        let expected = vec![
+            OperationArgs { gpio_index: 1, operation: Operation::Set(Level::High) },
+            OperationArgs { gpio_index: 2, operation: Operation::Get() },
+            OperationArgs { gpio_index: 3, operation: Operation::Count(true) },
+        ];
+
+        let result = parse_body_into_operations(body)
+            .expect("parse_body_into_operations function failed!");
+
+        assert_eq!(expected, result);
+
+        match parse_body_into_operations(br#"[{"pin":1,"op":"set"}]"#) {
+            Ok(_) => panic!("This call should never succeed!"),
+            Err(e) => println!("Got expected error: \"{}\"", e)
+        }
+    }
+
+    #[test]
+    fn test_simulated_pwm_pin() {
+        let simulated_pwm_pin = SimulatedPwmPin::new();
+        assert_eq!((0.0, 0.0), simulated_pwm_pin.get_pwm());
+
+        simulated_pwm_pin.set_pwm(1000.0, 0.5)
+            .expect("set_pwm function failed!");
+
+        assert_eq!((1000.0, 0.5), simulated_pwm_pin.get_pwm());
+    }
+
+    #[test]
+    fn test_parse_gpio_level_list() {
+        let level_map = parse_gpio_level_list("17=high,18=low")
+            .expect("parse_gpio_level_list function failed!");
+
+        assert_eq!(Level::High, level_map[&17]);
+        assert_eq!(Level::Low, level_map[&18]);
+
+        match parse_gpio_level_list("17=sideways") {
+            Ok(_) => panic!("This call should never succeed!"),
+            Err(e) => println!("Got expected error: \"{}\"", e)
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let secret = "top-secret";
+        let message = b"pin=1&op=get&ts=1000";
+
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(message);
+        let signature_hex: String = mac.finalize().into_bytes().iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        assert!(verify_signature(secret, message, &signature_hex));
+        assert!(!verify_signature(secret, message, "deadbeef"));
+        assert!(!verify_signature("wrong-secret", message, &signature_hex));
+    }
+
 }